@@ -0,0 +1,353 @@
+use std::cmp::max;
+
+use crate::semiglobal::{Cell, MatchFunc, Moves, Scoring, SemiglobalAlign, NEGATIVE_INF};
+
+/// Which flavour of affine-gap alignment `Aligner::align` computes.
+/// `Semiglobal` delegates straight to `SemiglobalAlign::compute` (the read
+/// `t` is aligned end to end except for optional soft clips at either end,
+/// and the alignment may start and end anywhere in the reference `s`).
+/// `Global` pins both `s` and `t` end-to-end, the classic
+/// Needleman-Wunsch problem. `Local` clamps cell scores at zero and reports
+/// the single best-scoring substring pair, the classic Smith-Waterman
+/// problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlignmentMode {
+    Global,
+    Local,
+    Semiglobal,
+}
+
+/// A single entry point over the three alignment flavours above. All three
+/// share the affine-gap scoring model and DP shape already used by
+/// `SemiglobalAlign::compute`, and all three hand back the same result type,
+/// so `pretty_print`, `cigar`, etc. work regardless of `mode`.
+pub struct Aligner<F : MatchFunc> {
+    scoring : Scoring<F>,
+    mode    : AlignmentMode,
+}
+
+impl<F : MatchFunc> Aligner<F> {
+    pub fn new(scoring : Scoring<F>, mode : AlignmentMode) -> Self {
+        Aligner { scoring, mode }
+    }
+
+    pub fn align(&self, s: &[u8], t: &[u8]) -> SemiglobalAlign {
+        match self.mode {
+            AlignmentMode::Semiglobal => SemiglobalAlign::compute(s, t, &self.scoring),
+            AlignmentMode::Global => Aligner::align_global(s, t, &self.scoring),
+            AlignmentMode::Local => Aligner::align_local(s, t, &self.scoring),
+        }
+    }
+
+    // Needleman-Wunsch: both endpoints pinned, no clipping. Structurally the
+    // same three-matrix affine-gap recurrence as `SemiglobalAlign::compute`,
+    // minus the soft-clip options and the "pick the best end row" scan --
+    // the traceback always starts at `(m-1, n-1)`.
+    //
+    // `I[i][j]`/`D[i][j]` follow `compute`'s convention: the "continue an
+    // existing gap" branch is tagged with a literal `Moves::INSERT`/
+    // `Moves::DELETE`, but the "open a new gap from S" branch is tagged with
+    // *`S`'s own predecessor move*, not a literal. That's what lets
+    // `traceback` tell, once it's walking an I or D run, whether the cell it
+    // just stepped into continues the same run (so it should keep reading
+    // that matrix) or is where the run was opened (so it should switch to
+    // whatever `S` actually came from there) -- re-reading `S[i][j].mov` at
+    // every step instead conflates the two and can hand back a move list
+    // that doesn't replay to the reported score.
+    #[allow(non_snake_case)]
+    fn align_global(s: &[u8], t: &[u8], scoring: &Scoring<F>) -> SemiglobalAlign {
+        let m = s.len() + 1;
+        let n = t.len() + 1;
+
+        let mut M = vec![vec![Cell{score: NEGATIVE_INF, mov: Moves::NONE}; n]; m];
+        let mut I = vec![vec![Cell{score: NEGATIVE_INF, mov: Moves::NONE}; n]; m];
+        let mut D = vec![vec![Cell{score: NEGATIVE_INF, mov: Moves::NONE}; n]; m];
+        let mut S = vec![vec![Cell{score: NEGATIVE_INF, mov: Moves::NONE}; n]; m];
+
+        S[0][0] = Cell{score: 0, mov: Moves::NONE};
+
+        for i in 0..m {
+            let (x, diag) = if i > 0 {
+                let x = s[i - 1];
+                (x, scoring.match_fn.score(x, x))
+            } else {
+                (0, 0)
+            };
+            for j in 0..n {
+                if i == 0 && j == 0 { continue; }
+
+                if i > 0 {
+                    I[i][j] = max(Cell{score: I[i - 1][j].score + scoring.gap_unit_score, mov: Moves::INSERT},
+                        Cell{score: S[i - 1][j].score + scoring.gap_inititation_score + scoring.gap_unit_score, mov: S[i - 1][j].mov});
+                }
+                if j > 0 {
+                    D[i][j] = max(Cell{score: D[i][j - 1].score + scoring.gap_unit_score, mov: Moves::DELETE},
+                        Cell{score: S[i][j - 1].score + scoring.gap_inititation_score + scoring.gap_unit_score, mov: S[i][j - 1].mov});
+                }
+
+                let mov_candidate = if i > 0 && j > 0 {
+                    let y = t[j - 1];
+                    let mov_kind = SemiglobalAlign::match_move(scoring, diag, x, y);
+                    M[i][j] = Cell{score: S[i - 1][j - 1].score + scoring.match_fn.score(x, y), mov: S[i - 1][j - 1].mov};
+                    Some(Cell{score: M[i][j].score, mov: mov_kind})
+                } else {
+                    None
+                };
+
+                let i_cell = Cell{score: I[i][j].score, mov: Moves::INSERT};
+                let d_cell = Cell{score: D[i][j].score, mov: Moves::DELETE};
+                S[i][j] = match (i > 0, j > 0, mov_candidate) {
+                    (true, true, Some(m_cell)) => max(max(i_cell, d_cell), m_cell),
+                    (true, false, _) => i_cell,
+                    (false, true, _) => d_cell,
+                    _ => unreachable!("(0, 0) is skipped above"),
+                };
+            }
+        }
+
+        traceback(Matrices{M: &M, I: &I, D: &D, S: &S}, (m - 1, n - 1), s, t, scoring)
+    }
+
+    // Smith-Waterman: same recurrence as `align_global`, but a cell can
+    // never score below zero -- which both caps how much a mismatch/gap run
+    // can drag the score down and marks the cell as a fresh alignment start
+    // (`Moves::NONE`) for the traceback below. The traceback starts at
+    // whichever cell scored highest anywhere in the matrix and walks back
+    // until it hits one of those zero-reset cells.
+    #[allow(non_snake_case)]
+    fn align_local(s: &[u8], t: &[u8], scoring: &Scoring<F>) -> SemiglobalAlign {
+        let m = s.len() + 1;
+        let n = t.len() + 1;
+
+        let mut M = vec![vec![Cell{score: NEGATIVE_INF, mov: Moves::NONE}; n]; m];
+        let mut I = vec![vec![Cell{score: NEGATIVE_INF, mov: Moves::NONE}; n]; m];
+        let mut D = vec![vec![Cell{score: NEGATIVE_INF, mov: Moves::NONE}; n]; m];
+        let mut S = vec![vec![Cell{score: 0, mov: Moves::NONE}; n]; m];
+
+        let mut best = (0usize, 0usize);
+
+        for i in 1..m {
+            let x = s[i - 1];
+            let diag = scoring.match_fn.score(x, x);
+            for j in 1..n {
+                let y = t[j - 1];
+
+                I[i][j] = max(Cell{score: I[i - 1][j].score + scoring.gap_unit_score, mov: Moves::INSERT},
+                    Cell{score: S[i - 1][j].score + scoring.gap_inititation_score + scoring.gap_unit_score, mov: S[i - 1][j].mov});
+                D[i][j] = max(Cell{score: D[i][j - 1].score + scoring.gap_unit_score, mov: Moves::DELETE},
+                    Cell{score: S[i][j - 1].score + scoring.gap_inititation_score + scoring.gap_unit_score, mov: S[i][j - 1].mov});
+
+                let mov_kind = SemiglobalAlign::match_move(scoring, diag, x, y);
+                M[i][j] = Cell{score: S[i - 1][j - 1].score + scoring.match_fn.score(x, y), mov: S[i - 1][j - 1].mov};
+
+                let i_cell = Cell{score: I[i][j].score, mov: Moves::INSERT};
+                let d_cell = Cell{score: D[i][j].score, mov: Moves::DELETE};
+                let candidate = max(max(i_cell, d_cell), Cell{score: M[i][j].score, mov: mov_kind});
+                S[i][j] = if candidate.score > 0 { candidate } else { Cell{score: 0, mov: Moves::NONE} };
+
+                if S[i][j].score > S[best.0][best.1].score {
+                    best = (i, j);
+                }
+            }
+        }
+
+        traceback(Matrices{M: &M, I: &I, D: &D, S: &S}, best, s, t, scoring)
+    }
+}
+
+// The four DP matrices `traceback` needs to walk, bundled into one
+// argument so the function stays under clippy's arg-count limit.
+#[allow(non_snake_case)]
+struct Matrices<'a> {
+    M : &'a [Vec<Cell>],
+    I : &'a [Vec<Cell>],
+    D : &'a [Vec<Cell>],
+    S : &'a [Vec<Cell>],
+}
+
+// `S[i][j].mov` only ever names the move that produced `S`'s score at that
+// cell (`INSERT`/`DELETE`/`MATCH`/`SUBS`/`NONE`) -- it says nothing about
+// which matrix to keep reading from one step back, since a gap run and a
+// fresh gap-open can land on the same literal tag. So, mirroring
+// `SemiglobalAlign::compute`'s own traceback, this walks `S[end].mov` once
+// to get started and then branches on the *current* move to decide which
+// matrix's `.mov` says what came before it: `M[i][j].mov` after a
+// MATCH/SUBS, `I[i][j].mov` after an INSERT, `D[i][j].mov` after a DELETE.
+fn traceback<F : MatchFunc>(matrices: Matrices, end: (usize, usize), s: &[u8], t: &[u8], scoring: &Scoring<F>) -> SemiglobalAlign {
+    let Matrices{M, I, D, S} = matrices;
+    let (mut i, mut j) = end;
+    let (end_i, end_j) = end;
+    let mut moves = Vec::new();
+
+    let mut last = S[i][j].mov;
+    if last != Moves::NONE {
+        moves.push(last);
+        loop {
+            let next = match last {
+                Moves::MATCH | Moves::SUBS => { let n = M[i][j].mov; i -= 1; j -= 1; n },
+                Moves::INSERT => { let n = I[i][j].mov; i -= 1; n },
+                Moves::DELETE => { let n = D[i][j].mov; j -= 1; n },
+                _ => unreachable!("global/local traceback only ever sees MATCH/SUBS/INSERT/DELETE"),
+            };
+            if next == Moves::NONE { break; }
+            moves.push(next);
+            last = next;
+        }
+    }
+    moves.reverse();
+
+    let local_scores = SemiglobalAlign::local_scores(s, t, scoring, &moves, i, j);
+
+    SemiglobalAlign {
+        score_matrix  : Vec::new(),
+        match_matrix  : Vec::new(),
+        insert_matrix : Vec::new(),
+        delete_matrix : Vec::new(),
+        clip_lengths  : Vec::new(),
+
+        score   : S[end_i][end_j].score,
+        s_range : [i as i32, end_i as i32],
+        t_range : [j as i32, end_j as i32],
+        moves,
+        local_scores,
+
+        prefix_clip_length : 0,
+        suffix_clip_length : 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semiglobal::Moves::*;
+
+    #[test]
+    fn global_exact_match() {
+        let scoring = Scoring::new(-5, -1, 1, -1, -100);
+        let aligner = Aligner::new(scoring, AlignmentMode::Global);
+        let align = aligner.align(b"ACGT", b"ACGT");
+        assert_eq!(align.score, 4);
+        assert_eq!(align.s_range, [0, 4]);
+        assert_eq!(align.t_range, [0, 4]);
+        assert_eq!(align.moves, vec![MATCH, MATCH, MATCH, MATCH]);
+    }
+
+    #[test]
+    fn global_pins_both_endpoints_through_a_gap() {
+        let scoring = Scoring::new(-5, -1, 1, -1, -100);
+        let aligner = Aligner::new(scoring, AlignmentMode::Global);
+        let align = aligner.align(b"ACGT", b"ACT");
+        assert_eq!(align.moves, vec![MATCH, MATCH, INSERT, MATCH]);
+        assert_eq!(align.score, -3);
+        assert_eq!(align.s_range, [0, 4]);
+        assert_eq!(align.t_range, [0, 3]);
+    }
+
+    #[test]
+    fn local_finds_embedded_match_ignoring_flanks() {
+        let scoring = Scoring::new(-5, -2, 2, -1, -100);
+        let aligner = Aligner::new(scoring, AlignmentMode::Local);
+        let align = aligner.align(b"TTACGTTT", b"GGACGTGG");
+        assert_eq!(align.score, 8);
+        assert_eq!(align.s_range, [2, 6]);
+        assert_eq!(align.t_range, [2, 6]);
+        assert_eq!(align.moves, vec![MATCH, MATCH, MATCH, MATCH]);
+        assert_eq!(align.local_scores, vec![2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn local_scores_sums_to_score_through_a_gap() {
+        let scoring = Scoring::new(-5, -1, 1, -1, -100);
+        let aligner = Aligner::new(scoring, AlignmentMode::Global);
+        let align = aligner.align(b"ACGT", b"ACT");
+        assert_eq!(align.local_scores.len(), align.moves.len());
+        assert_eq!(align.local_scores.iter().sum::<i32>(), align.score);
+    }
+
+    // Walks `moves` against `s`/`t` under the stated affine-gap rule and
+    // returns the total -- same idea as `semiglobal::tests::replay_score`,
+    // but starting from `s_range`/`t_range` directly since neither
+    // `Global` nor `Local` ever clips. A `score`/`moves` pair that's out of
+    // sync with each other (the traceback smuggling in a different,
+    // non-optimal path than the one the DP actually scored) fails this even
+    // when every hand-picked example test above happens to pass.
+    fn replay_score<F : MatchFunc>(s: &[u8], t: &[u8], scoring: &Scoring<F>, align: &SemiglobalAlign) -> i32 {
+        let mut score = 0;
+        let mut si = align.s_range[0] as usize;
+        let mut ti = align.t_range[0] as usize;
+        let mut prev_gap : Option<Moves> = None;
+
+        for &mov in &align.moves {
+            match mov {
+                Moves::MATCH | Moves::SUBS => {
+                    score += scoring.match_fn.score(s[si], t[ti]);
+                    si += 1;
+                    ti += 1;
+                    prev_gap = None;
+                },
+                Moves::INSERT => {
+                    score += if prev_gap == Some(Moves::INSERT) { scoring.gap_unit_score }
+                        else { scoring.gap_inititation_score + scoring.gap_unit_score };
+                    si += 1;
+                    prev_gap = Some(Moves::INSERT);
+                },
+                Moves::DELETE => {
+                    score += if prev_gap == Some(Moves::DELETE) { scoring.gap_unit_score }
+                        else { scoring.gap_inititation_score + scoring.gap_unit_score };
+                    ti += 1;
+                    prev_gap = Some(Moves::DELETE);
+                },
+                _ => panic!("global/local moves should never contain a clip"),
+            }
+        }
+
+        assert_eq!(si, align.s_range[1] as usize);
+        assert_eq!(ti, align.t_range[1] as usize);
+        score
+    }
+
+    // A handful of inputs picked to force a gap chain to run through a cell
+    // whose own S-best didn't come from that chain -- exactly the situation
+    // that used to make `traceback` smuggle in a cheaper-looking but
+    // inconsistent move list. Covers both modes; the first case uses a
+    // `mismatch_score` of 0 and equal gap-open and gap-unit costs.
+    // (gap_init, gap_unit, match_score, mismatch_score, soft_clip) -- kept as
+    // raw params rather than a built `Scoring` since `Aligner::new` consumes
+    // it and `replay_score` needs its own, and `Scoring` isn't `Clone`.
+    type CaseParams = (i32, i32, i32, i32, i32);
+
+    #[test]
+    fn global_moves_replay_to_the_reported_score() {
+        let cases : Vec<(&[u8], &[u8], CaseParams)> = vec![
+            (b"AT", b"GCATG", (-3, -3, 1, 0, -9)),
+            (b"ACGT", b"ACT", (-5, -1, 1, -1, -100)),
+            (b"GGTAGGG", b"GGGGG", (-5, -1, 1, -3, -100)),
+            (b"ATAG", b"GGGGGGATG", (-5, -1, 1, -1, -5)),
+            (b"CGTTTT", b"GAAAA", (-5, -1, 2, -2, -5)),
+            (b"TTTT", b"AAAA", (-5, -1, 1, -3, -100)),
+        ];
+        for (s, t, (gi, gu, ms, mm, sc)) in cases {
+            let align = Aligner::new(Scoring::new(gi, gu, ms, mm, sc), AlignmentMode::Global).align(s, t);
+            let scoring = Scoring::new(gi, gu, ms, mm, sc);
+            assert_eq!(replay_score(s, t, &scoring, &align), align.score, "s={:?} t={:?}", s, t);
+        }
+    }
+
+    #[test]
+    fn local_moves_replay_to_the_reported_score() {
+        let cases : Vec<(&[u8], &[u8], CaseParams)> = vec![
+            (b"AT", b"GCATG", (-3, -3, 1, 0, -9)),
+            (b"TTACGTTT", b"GGACGTGG", (-5, -2, 2, -1, -100)),
+            (b"GGTAGGG", b"GGGGG", (-5, -1, 1, -3, -100)),
+            (b"ATAG", b"GGGGGGATG", (-5, -1, 1, -1, -5)),
+            (b"CGTTTT", b"GAAAA", (-5, -1, 2, -2, -5)),
+            (b"GGGGGGATTTCCCCCCCCCTTTTTTTTTTAAAAAAAAA", b"TTTTTGGGGGGATGGCCCCCCTTTTTTTTTTGGGAAAAAAAAAGGGGGG", (-5, -1, 2, -2, -5)),
+        ];
+        for (s, t, (gi, gu, ms, mm, sc)) in cases {
+            let align = Aligner::new(Scoring::new(gi, gu, ms, mm, sc), AlignmentMode::Local).align(s, t);
+            if align.moves.is_empty() { continue; }
+            let scoring = Scoring::new(gi, gu, ms, mm, sc);
+            assert_eq!(replay_score(s, t, &scoring, &align), align.score, "s={:?} t={:?}", s, t);
+        }
+    }
+}